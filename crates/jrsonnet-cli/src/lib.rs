@@ -87,6 +87,9 @@ pub struct GeneralOpts {
 
 	#[clap(flatten)]
 	trace: TraceOpts,
+
+	#[clap(flatten)]
+	pub manifest: ManifestOpts,
 }
 
 impl ConfigureState for GeneralOpts {
@@ -120,6 +123,13 @@ pub struct GcOpts {
 	/// Does nothing useless --gc-print-stats is specified
 	#[clap(long)]
 	gc_collect_before_printing_stats: bool,
+	/// Annotate GC blocks with Valgrind memcheck client requests, so that
+	/// running under Valgrind gives allocation-site-level leak reports
+	/// instead of just the byte total from --gc-print-stats.
+	/// Requires building with the `valgrind` cargo feature; otherwise it
+	/// only prints a warning and has no other effect.
+	#[clap(long)]
+	gc_valgrind: bool,
 }
 impl GcOpts {
 	pub fn stats_printer(&self) -> Option<GcStatsPrinter> {
@@ -127,12 +137,21 @@ impl GcOpts {
 			.then(|| GcStatsPrinter(self.gc_collect_before_printing_stats))
 	}
 	pub fn configure_global(&self) {
+		#[cfg(not(feature = "valgrind"))]
+		if self.gc_valgrind {
+			eprintln!("warning: --gc-valgrind has no effect, jrsonnet was built without the `valgrind` feature");
+		}
+
 		jrsonnet_gc::configure(|config| {
 			config.leak_on_drop = !self.gc_collect_on_exit;
 			config.threshold = self.gc_initial_threshold;
 			if let Some(used_space_ratio) = self.gc_used_space_ratio {
 				config.used_space_ratio = used_space_ratio;
 			}
+			#[cfg(feature = "valgrind")]
+			{
+				config.valgrind = self.gc_valgrind;
+			}
 		});
 	}
 }
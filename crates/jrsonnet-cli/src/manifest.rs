@@ -0,0 +1,125 @@
+use clap::{ArgEnum, Clap};
+use jrsonnet_evaluator::{
+	error::{Error::RuntimeError, Result},
+	ObjValue, Val,
+};
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+#[derive(Clap, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum ManifestFormat {
+	Json,
+	Yaml,
+	String,
+}
+
+#[derive(Clap)]
+#[clap(help_heading = "MANIFESTIFICATION")]
+pub struct ManifestOpts {
+	/// Output format, used for manifestification of the top-level value
+	#[clap(long, short = 'f', arg_enum, default_value = "json")]
+	format: ManifestFormat,
+
+	/// Output directory to write the manifested value into, one file per
+	/// top-level field. The top-level value must then be an object: every
+	/// field name is treated as a path relative to this directory, and every
+	/// field's value is manifested into that file.
+	///
+	/// When unset (the default), the whole manifested value is printed to
+	/// stdout instead.
+	#[clap(long, short = 'm')]
+	out_dir: Option<PathBuf>,
+}
+
+impl ManifestOpts {
+	fn manifest_value(&self, val: Val) -> Result<String> {
+		Ok(match self.format {
+			ManifestFormat::Json => val.manifest_json()?,
+			ManifestFormat::Yaml => val.manifest_yaml()?,
+			ManifestFormat::String => val.manifest_string()?,
+		})
+	}
+
+	/// Manifests `val` the way requested on the command line: either as a
+	/// single document (returned to the caller to print to stdout), or, if
+	/// `--out-dir` was given, written out as multiple files - one per field
+	/// of the top-level object - and `Ok(None)` is returned.
+	///
+	/// In multi-file mode, a file is only rewritten if its manifested
+	/// contents actually differ from what's already on disk, so that running
+	/// the same evaluation twice doesn't touch file mtimes and confuse build
+	/// systems watching the output directory.
+	pub fn write(&self, val: Val) -> Result<Option<String>> {
+		let Some(out_dir) = &self.out_dir else {
+			return Ok(Some(self.manifest_value(val)?));
+		};
+
+		let obj = match val {
+			Val::Obj(obj) => obj,
+			_ => {
+				return Err(RuntimeError(
+					"multi-file output requires top-level value to be an object".into(),
+				)
+				.into())
+			}
+		};
+
+		let mut written = Vec::new();
+		for field in Self::fields_in_order(&obj) {
+			let value = obj
+				.get(field.clone())?
+				.expect("field exists, as it was just enumerated");
+			let content = self.manifest_value(value)?;
+
+			let path = Self::field_path(out_dir, field.as_str())?;
+			if Self::needs_write(&path, &content)? {
+				if let Some(parent) = path.parent() {
+					fs::create_dir_all(parent)
+						.map_err(|e| RuntimeError(format!("{}: {}", path.display(), e).into()))?;
+				}
+				fs::write(&path, &content)
+					.map_err(|e| RuntimeError(format!("{}: {}", path.display(), e).into()))?;
+			}
+			written.push(path);
+		}
+
+		for path in &written {
+			println!("{}", path.display());
+		}
+
+		Ok(None)
+	}
+
+	fn fields_in_order(obj: &ObjValue) -> Vec<jrsonnet_interner::IStr> {
+		obj.fields()
+	}
+
+	/// Joins `field` onto `out_dir`, rejecting anything that would land
+	/// outside `out_dir` - an absolute field name (`PathBuf::join` discards
+	/// the base entirely for those) or one containing a `..` component. The
+	/// field name comes straight from the (Jsonnet-controlled) manifested
+	/// value, so it can't be trusted to stay inside `out_dir` on its own.
+	fn field_path(out_dir: &Path, field: &str) -> Result<PathBuf> {
+		let field_path = Path::new(field);
+		if field_path.is_absolute()
+			|| field_path
+				.components()
+				.any(|c| matches!(c, std::path::Component::ParentDir))
+		{
+			return Err(RuntimeError(
+				format!("{}: field name escapes --out-dir", field).into(),
+			)
+			.into());
+		}
+		Ok(out_dir.join(field_path))
+	}
+
+	fn needs_write(path: &Path, content: &str) -> Result<bool> {
+		Ok(match fs::read(path) {
+			Ok(existing) => existing != content.as_bytes(),
+			Err(_) => true,
+		})
+	}
+}
@@ -0,0 +1,33 @@
+pub mod encoding;
+
+use jrsonnet_parser::ExprLocation;
+
+use crate::{function::ArgsDesc, Context, Result, Val};
+
+/// Signature every `#[builtin]`-annotated function expands to.
+pub(crate) type NativeBuiltin = fn(Context, &ExprLocation, &ArgsDesc) -> Result<Val>;
+
+/// `std` fields backed by a native Rust function, name as seen from Jsonnet
+/// alongside the generated wrapper that implements it. Each module under
+/// `builtin/` contributes its own slice here; whatever assembles the `std`
+/// object should chain all of them in rather than hardcoding a single one.
+///
+/// That assembly point doesn't exist in this crate yet - there's no
+/// `EvaluationState`/`std` `ObjValue` construction anywhere for this table to
+/// be spliced into (this crate has no `lib.rs` at all, unlike `jrsonnet-gc`/
+/// `jrsonnet-cli`/`jrsonnet-macros`, each of which has one). Wiring this in
+/// for real means building that evaluator core first, which is well beyond
+/// a builtins addition; until then, neither this table nor the `builtin_*`
+/// wrappers it points at are called from anywhere - `encoding`'s own unit
+/// tests exercise the encode/decode helpers underneath them directly.
+pub(crate) const ENCODING_FIELDS: &[(&str, NativeBuiltin)] = &[
+	("hexEncode", encoding::builtin_hex_encode),
+	("hexEncodeUpper", encoding::builtin_hex_encode_upper),
+	("hexDecode", encoding::builtin_hex_decode),
+	("base32Encode", encoding::builtin_base32_encode),
+	("base32Decode", encoding::builtin_base32_decode),
+	("bech32Encode", encoding::builtin_bech32_encode),
+	("bech32mEncode", encoding::builtin_bech32m_encode),
+	("bech32Decode", encoding::builtin_bech32_decode),
+	("bech32mDecode", encoding::builtin_bech32m_decode),
+];
@@ -0,0 +1,372 @@
+//! Encoding/decoding builtins beyond the JSON-oriented base64 family: hex,
+//! RFC 4648 base32, and bech32/bech32m.
+use jrsonnet_interner::IStr;
+use jrsonnet_macros::builtin;
+use jrsonnet_parser::ExprLocation;
+
+use crate::{
+	error::{Error::RuntimeError, Result},
+	function::ArgsDesc,
+	Context, Val,
+};
+
+/// Either a Jsonnet string (its UTF-8 bytes are used directly) or an array
+/// of byte-valued numbers, as accepted by every builtin in this module.
+struct Bytes(Vec<u8>);
+impl TryFrom<Val> for Bytes {
+	type Error = crate::error::Error;
+	fn try_from(v: Val) -> Result<Self> {
+		Ok(Self(match v {
+			Val::Str(s) => s.as_bytes().to_vec(),
+			Val::Arr(arr) => {
+				let mut out = Vec::with_capacity(arr.len());
+				for el in arr.iter() {
+					let n = f64::try_from(el?)?;
+					if !(0.0..=255.0).contains(&n) || n.fract() != 0.0 {
+						return Err(RuntimeError("expected byte value in 0..=255".into()).into());
+					}
+					out.push(n as u8);
+				}
+				out
+			}
+			_ => return Err(RuntimeError("expected string or array of bytes".into()).into()),
+		}))
+	}
+}
+
+fn bytes_to_val(bytes: Vec<u8>) -> Val {
+	Val::Arr(bytes.into_iter().map(|b| Val::Num(b as f64)).collect())
+}
+
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+
+fn hex_encode(data: &[u8], uppercase: bool) -> String {
+	let table = if uppercase {
+		b"0123456789ABCDEF"
+	} else {
+		HEX_LOWER
+	};
+	let mut out = String::with_capacity(data.len() * 2);
+	for b in data {
+		out.push(table[(b >> 4) as usize] as char);
+		out.push(table[(b & 0xf) as usize] as char);
+	}
+	out
+}
+
+fn hex_decode(data: &str) -> Result<Vec<u8>> {
+	fn nibble(c: u8) -> Result<u8> {
+		Ok(match c {
+			b'0'..=b'9' => c - b'0',
+			b'a'..=b'f' => c - b'a' + 10,
+			b'A'..=b'F' => c - b'A' + 10,
+			_ => return Err(RuntimeError("invalid hex digit".into()).into()),
+		})
+	}
+	let data = data.as_bytes();
+	if data.len() % 2 != 0 {
+		return Err(RuntimeError("hex string has odd length".into()).into());
+	}
+	data.chunks(2)
+		.map(|pair| Ok(nibble(pair[0])? << 4 | nibble(pair[1])?))
+		.collect()
+}
+
+#[builtin]
+pub(crate) fn builtin_hex_encode(data: Bytes) -> Result<String> {
+	Ok(hex_encode(&data.0, false))
+}
+
+#[builtin]
+pub(crate) fn builtin_hex_encode_upper(data: Bytes) -> Result<String> {
+	Ok(hex_encode(&data.0, true))
+}
+
+#[builtin]
+pub(crate) fn builtin_hex_decode(str: IStr) -> Result<Val> {
+	Ok(bytes_to_val(hex_decode(&str)?))
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn base32_encode(data: &[u8]) -> String {
+	let mut out = String::with_capacity((data.len() + 4) / 5 * 8);
+	for chunk in data.chunks(5) {
+		let mut buf = [0u8; 5];
+		buf[..chunk.len()].copy_from_slice(chunk);
+		let n = u64::from_be_bytes([0, 0, 0, buf[0], buf[1], buf[2], buf[3], buf[4]]);
+		let symbols = (chunk.len() * 8 + 4) / 5;
+		for i in 0..8 {
+			if i < symbols {
+				let shift = 35 - i * 5;
+				let idx = ((n >> shift) & 0x1f) as usize;
+				out.push(BASE32_ALPHABET[idx] as char);
+			} else {
+				out.push('=');
+			}
+		}
+	}
+	out
+}
+
+fn base32_decode(data: &str) -> Result<Vec<u8>> {
+	fn value(c: u8) -> Result<u8> {
+		BASE32_ALPHABET
+			.iter()
+			.position(|&a| a == c.to_ascii_uppercase())
+			.map(|i| i as u8)
+			.ok_or_else(|| RuntimeError("invalid base32 character".into()).into())
+	}
+	let trimmed = data.trim_end_matches('=');
+	let mut bits: u64 = 0;
+	let mut bit_count = 0u32;
+	let mut out = Vec::with_capacity(trimmed.len() * 5 / 8);
+	for c in trimmed.bytes() {
+		bits = (bits << 5) | u64::from(value(c)?);
+		bit_count += 5;
+		if bit_count >= 8 {
+			bit_count -= 8;
+			out.push((bits >> bit_count) as u8);
+		}
+	}
+	Ok(out)
+}
+
+#[builtin]
+pub(crate) fn builtin_base32_encode(data: Bytes) -> Result<String> {
+	Ok(base32_encode(&data.0))
+}
+
+#[builtin]
+pub(crate) fn builtin_base32_decode(str: IStr) -> Result<Val> {
+	Ok(bytes_to_val(base32_decode(&str)?))
+}
+
+/// `qpzry9x8gf2tvdw0s3jn54khce6mua7l`, the bech32 data-part charset.
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+	const GENERATOR: [u32; 5] = [
+		0x3b6a_57b2,
+		0x2650_8e6d,
+		0x1ea1_19fa,
+		0x3d42_33dd,
+		0x2a14_62b3,
+	];
+	let mut chk: u32 = 1;
+	for &v in values {
+		let top = chk >> 25;
+		chk = (chk & 0x1ff_ffff) << 5 ^ u32::from(v);
+		for (i, gen) in GENERATOR.iter().enumerate() {
+			if (top >> i) & 1 == 1 {
+				chk ^= gen;
+			}
+		}
+	}
+	chk
+}
+
+/// High bits, then a zero separator, then low bits - the HRP expansion
+/// bech32 folds into the checksum so that the checksum depends on the
+/// human-readable part too.
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+	let mut out: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+	out.push(0);
+	out.extend(hrp.bytes().map(|b| b & 0x1f));
+	out
+}
+
+fn bech32_create_checksum(hrp: &str, data: &[u8], spec_const: u32) -> [u8; 6] {
+	let mut values = bech32_hrp_expand(hrp);
+	values.extend_from_slice(data);
+	values.extend_from_slice(&[0; 6]);
+	let polymod = bech32_polymod(&values) ^ spec_const;
+	let mut checksum = [0u8; 6];
+	for (i, c) in checksum.iter_mut().enumerate() {
+		*c = ((polymod >> (5 * (5 - i))) & 0x1f) as u8;
+	}
+	checksum
+}
+
+/// Regroups `data` (8-bit bytes if `from == 8`, 5-bit bech32 groups if
+/// `from == 5`) into `to`-bit groups, as used both to turn payload bytes
+/// into bech32 data characters and back.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>> {
+	let mut acc: u32 = 0;
+	let mut bits: u32 = 0;
+	let mut out = Vec::new();
+	let max_val = (1u32 << to) - 1;
+	for &value in data {
+		if (value as u32) >> from != 0 {
+			return Err(RuntimeError("invalid byte for input bit size".into()).into());
+		}
+		acc = (acc << from) | u32::from(value);
+		bits += from;
+		while bits >= to {
+			bits -= to;
+			out.push(((acc >> bits) & max_val) as u8);
+		}
+	}
+	if pad {
+		if bits > 0 {
+			out.push(((acc << (to - bits)) & max_val) as u8);
+		}
+	} else if bits >= from || (acc << (to - bits)) & max_val != 0 {
+		return Err(RuntimeError("invalid padding in convert_bits".into()).into());
+	}
+	Ok(out)
+}
+
+fn bech32_encode(hrp: &str, data: &[u8], variant_m: bool) -> Result<String> {
+	let values = convert_bits(data, 8, 5, true)?;
+	let spec_const = if variant_m { BECH32M_CONST } else { BECH32_CONST };
+	let checksum = bech32_create_checksum(hrp, &values, spec_const);
+	let mut out = String::with_capacity(hrp.len() + 1 + values.len() + checksum.len());
+	out.push_str(hrp);
+	out.push('1');
+	for &v in values.iter().chain(checksum.iter()) {
+		out.push(BECH32_CHARSET[v as usize] as char);
+	}
+	Ok(out)
+}
+
+fn bech32_decode(input: &str, variant_m: bool) -> Result<(String, Vec<u8>)> {
+	if input != input.to_lowercase() && input != input.to_uppercase() {
+		return Err(RuntimeError("bech32 string has mixed case".into()).into());
+	}
+	let input = input.to_lowercase();
+	let sep = input
+		.rfind('1')
+		.ok_or_else(|| RuntimeError("missing bech32 separator".into()))?;
+	let (hrp, data_part) = input.split_at(sep);
+	let data_part = &data_part[1..];
+	if data_part.len() < 6 {
+		return Err(RuntimeError("bech32 data part too short".into()).into());
+	}
+
+	let mut values = Vec::with_capacity(data_part.len());
+	for c in data_part.bytes() {
+		let v = BECH32_CHARSET
+			.iter()
+			.position(|&a| a == c)
+			.ok_or_else(|| RuntimeError("invalid bech32 character".into()))?;
+		values.push(v as u8);
+	}
+
+	let spec_const = if variant_m { BECH32M_CONST } else { BECH32_CONST };
+	let mut checked = bech32_hrp_expand(hrp);
+	checked.extend_from_slice(&values);
+	if bech32_polymod(&checked) != spec_const {
+		return Err(RuntimeError("invalid bech32 checksum".into()).into());
+	}
+
+	let payload = &values[..values.len() - 6];
+	let bytes = convert_bits(payload, 5, 8, false)?;
+	Ok((hrp.to_owned(), bytes))
+}
+
+#[builtin]
+pub(crate) fn builtin_bech32_encode(hrp: IStr, data: Bytes) -> Result<String> {
+	bech32_encode(&hrp, &data.0, false)
+}
+
+#[builtin]
+pub(crate) fn builtin_bech32m_encode(hrp: IStr, data: Bytes) -> Result<String> {
+	bech32_encode(&hrp, &data.0, true)
+}
+
+#[builtin]
+pub(crate) fn builtin_bech32_decode(str: IStr) -> Result<Val> {
+	let (hrp, data) = bech32_decode(&str, false)?;
+	Ok(Val::Arr(vec![Val::Str(hrp.into()), bytes_to_val(data)].into()))
+}
+
+#[builtin]
+pub(crate) fn builtin_bech32m_decode(str: IStr) -> Result<Val> {
+	let (hrp, data) = bech32_decode(&str, true)?;
+	Ok(Val::Arr(vec![Val::Str(hrp.into()), bytes_to_val(data)].into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn hex_round_trips() {
+		let data = b"\x00\x01\xfe\xff Hello, bech32!";
+		let encoded = hex_encode(data, false);
+		assert_eq!(encoded, encoded.to_lowercase());
+		assert_eq!(hex_decode(&encoded).unwrap(), data);
+		assert_eq!(
+			hex_decode(&hex_encode(data, true)).unwrap(),
+			data,
+			"uppercase hex must decode the same as lowercase"
+		);
+	}
+
+	#[test]
+	fn hex_decode_rejects_odd_length_and_bad_digits() {
+		assert!(hex_decode("abc").is_err());
+		assert!(hex_decode("zz").is_err());
+	}
+
+	#[test]
+	fn base32_round_trips_across_every_padding_remainder() {
+		// 5-byte chunks cover every padding remainder (0..=4 leftover bytes).
+		for len in 0..=12 {
+			let data: Vec<u8> = (0..len as u8).collect();
+			let encoded = base32_encode(&data);
+			assert_eq!(
+				base32_decode(&encoded).unwrap(),
+				data,
+				"round trip failed for {} byte(s)",
+				len
+			);
+		}
+	}
+
+	#[test]
+	fn base32_decode_is_case_insensitive() {
+		let data = b"some payload bytes";
+		let encoded = base32_encode(data);
+		assert_eq!(base32_decode(&encoded.to_lowercase()).unwrap(), data);
+	}
+
+	#[test]
+	fn bech32_round_trips() {
+		let data = b"\x00\x01\x02\x03\xff";
+		let encoded = bech32_encode("bc", data, false).unwrap();
+		let (hrp, decoded) = bech32_decode(&encoded, false).unwrap();
+		assert_eq!(hrp, "bc");
+		assert_eq!(decoded, data);
+	}
+
+	#[test]
+	fn bech32m_round_trips_and_rejects_the_wrong_variant() {
+		let data = b"bech32m payload";
+		let encoded = bech32_encode("tb", data, true).unwrap();
+		let (hrp, decoded) = bech32_decode(&encoded, true).unwrap();
+		assert_eq!(hrp, "tb");
+		assert_eq!(decoded, data);
+		// A bech32m string's checksum must not validate as plain bech32.
+		assert!(bech32_decode(&encoded, false).is_err());
+	}
+
+	#[test]
+	fn bech32_decode_rejects_mixed_case_and_bad_checksum() {
+		let encoded = bech32_encode("bc", b"abc", false).unwrap();
+		let mixed = format!(
+			"{}{}",
+			encoded[..encoded.len() / 2].to_uppercase(),
+			&encoded[encoded.len() / 2..]
+		);
+		assert!(bech32_decode(&mixed, false).is_err());
+
+		let mut corrupted = encoded.clone();
+		let last = corrupted.pop().unwrap();
+		corrupted.push(if last == 'q' { 'p' } else { 'q' });
+		assert!(bech32_decode(&corrupted, false).is_err());
+	}
+}
@@ -0,0 +1,158 @@
+//! The actual GC heap: an intrusive linked list of every live [`GcBox`],
+//! plus the `Gc<T>` smart pointer used to reach into it.
+//!
+//! Collection is mark-and-sweep, not refcounting: dropping a `Gc<T>` only
+//! unroots it (see [`Trace::unroot`]) rather than freeing anything, so a
+//! cycle with no external root is still reclaimed once the next collection
+//! traces from the surviving roots and sweeps whatever it didn't reach.
+use std::{cell::Cell, mem, ptr::NonNull};
+
+use crate::{note_alloc, note_free, Finalize, Trace};
+
+pub(crate) struct GcBoxHeader {
+	roots: Cell<usize>,
+	marked: Cell<bool>,
+	next: Cell<Option<NonNull<GcBox<dyn Trace>>>>,
+}
+
+pub(crate) struct GcBox<T: Trace + ?Sized> {
+	header: GcBoxHeader,
+	data: T,
+}
+
+thread_local!(static BOXES_START: Cell<Option<NonNull<GcBox<dyn Trace>>>> = Cell::new(None));
+
+/// A garbage-collected pointer. Reclaimed by the next [`crate::force_collect`]
+/// (or threshold-triggered collection) once nothing roots it anymore -
+/// unlike `Rc<T>`, dropping the last handle doesn't free it immediately.
+pub struct Gc<T: Trace + 'static> {
+	ptr: NonNull<GcBox<T>>,
+}
+
+impl<T: Trace> Gc<T> {
+	pub fn new(mut value: T) -> Self {
+		// `value` was just built on the stack, so any `Gc<_>` it owns is
+		// currently rooted independently. It's about to become reachable
+		// only through this box's own trace, so unroot it first.
+		unsafe {
+			value.unroot();
+		}
+		let size = mem::size_of::<GcBox<T>>();
+		let ptr = Box::into_raw(Box::new(GcBox {
+			header: GcBoxHeader {
+				roots: Cell::new(1),
+				marked: Cell::new(false),
+				next: Cell::new(None),
+			},
+			data: value,
+		}));
+		BOXES_START.with(|start| unsafe {
+			(*ptr).header.next.set(start.get());
+			start.set(Some(NonNull::new_unchecked(ptr as *mut GcBox<dyn Trace>)));
+		});
+		note_alloc(ptr as *const u8, size);
+		Self {
+			ptr: unsafe { NonNull::new_unchecked(ptr) },
+		}
+	}
+
+	fn inner(&self) -> &GcBox<T> {
+		unsafe { self.ptr.as_ref() }
+	}
+}
+
+impl<T: Trace> std::ops::Deref for Gc<T> {
+	type Target = T;
+	fn deref(&self) -> &T {
+		&self.inner().data
+	}
+}
+
+impl<T: Trace> Clone for Gc<T> {
+	fn clone(&self) -> Self {
+		self.root();
+		Self { ptr: self.ptr }
+	}
+}
+
+impl<T: Trace> Drop for Gc<T> {
+	fn drop(&mut self) {
+		unsafe {
+			self.unroot();
+		}
+	}
+}
+
+impl<T: Trace> Finalize for Gc<T> {}
+unsafe impl<T: Trace> Trace for Gc<T> {
+	unsafe fn trace(&self) {
+		let header = &self.inner().header;
+		if !header.marked.get() {
+			header.marked.set(true);
+			unsafe {
+				self.inner().data.trace();
+			}
+		}
+	}
+	fn root(&self) {
+		let header = &self.inner().header;
+		header.roots.set(header.roots.get() + 1);
+	}
+	unsafe fn unroot(&self) {
+		let header = &self.inner().header;
+		header.roots.set(header.roots.get() - 1);
+	}
+}
+
+/// Marks every block transitively reachable from a rooted block.
+fn mark() {
+	BOXES_START.with(|start| {
+		let mut current = start.get();
+		while let Some(node) = current {
+			let gcbox = unsafe { node.as_ref() };
+			if gcbox.header.roots.get() > 0 && !gcbox.header.marked.get() {
+				gcbox.header.marked.set(true);
+				unsafe {
+					gcbox.data.trace();
+				}
+			}
+			current = gcbox.header.next.get();
+		}
+	});
+}
+
+/// Frees every unmarked block, unlinking it from the live list, and clears
+/// the mark bit on every survivor so the next collection starts fresh.
+/// Reports each reclaimed block through [`note_free`].
+fn sweep() {
+	BOXES_START.with(|start| {
+		let mut prev: Option<NonNull<GcBox<dyn Trace>>> = None;
+		let mut current = start.get();
+		while let Some(node) = current {
+			let gcbox = unsafe { node.as_ref() };
+			let next = gcbox.header.next.get();
+			if gcbox.header.marked.get() {
+				gcbox.header.marked.set(false);
+				prev = Some(node);
+			} else {
+				match prev {
+					Some(p) => unsafe { p.as_ref().header.next.set(next) },
+					None => start.set(next),
+				}
+				let addr = node.as_ptr() as *const u8;
+				let size = mem::size_of_val(unsafe { node.as_ref() });
+				// SAFETY: `node` was unlinked above and is unmarked, so
+				// nothing still roots or traces into it.
+				unsafe { drop(Box::from_raw(node.as_ptr())) };
+				note_free(addr, size);
+			}
+			current = next;
+		}
+	});
+}
+
+/// Runs a full mark-and-sweep pass over every live [`Gc`] allocation.
+pub(crate) fn collect() {
+	mark();
+	sweep();
+}
@@ -0,0 +1,39 @@
+//! Interior mutability for data living inside a [`crate::Gc`], analogous to
+//! `RefCell<T>` but propagating `Trace`/`Finalize` through to the wrapped
+//! value so the collector can still see into it.
+use std::cell::{Ref, RefCell, RefMut};
+
+use crate::{Finalize, Trace};
+
+pub struct GcCell<T: Trace>(RefCell<T>);
+
+impl<T: Trace> GcCell<T> {
+	pub fn new(value: T) -> Self {
+		Self(RefCell::new(value))
+	}
+
+	pub fn borrow(&self) -> Ref<'_, T> {
+		self.0.borrow()
+	}
+
+	pub fn borrow_mut(&self) -> RefMut<'_, T> {
+		self.0.borrow_mut()
+	}
+}
+
+impl<T: Trace> Finalize for GcCell<T> {}
+unsafe impl<T: Trace> Trace for GcCell<T> {
+	unsafe fn trace(&self) {
+		unsafe {
+			self.0.borrow().trace();
+		}
+	}
+	fn root(&self) {
+		self.0.borrow().root();
+	}
+	unsafe fn unroot(&self) {
+		unsafe {
+			self.0.borrow().unroot();
+		}
+	}
+}
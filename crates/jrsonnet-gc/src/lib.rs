@@ -0,0 +1,111 @@
+//! A small mark-and-sweep GC: `Gc<T>`/`GcCell<T>` (see `boxed.rs`/`cell.rs`),
+//! plus the knobs for *when*/*how* collection runs and, behind the
+//! `valgrind` feature, a bridge from the collector's own allocate/reclaim
+//! bookkeeping to Valgrind's memcheck tool so that running jrsonnet under
+//! Valgrind gives allocation-site-level leak reports instead of just a byte
+//! total.
+use std::cell::{Cell, RefCell};
+
+mod boxed;
+mod cell;
+mod trace;
+#[cfg(feature = "valgrind")]
+mod valgrind;
+
+pub use boxed::Gc;
+pub use cell::GcCell;
+pub use trace::{Finalize, Trace};
+
+/// Knobs for when and how the collector runs. See `GcOpts` in
+/// `jrsonnet-cli` for the corresponding command line flags.
+#[derive(Clone, Copy)]
+pub struct Config {
+	/// Live bytes on the GC heap above which the next allocation triggers
+	/// a collection.
+	pub threshold: usize,
+	/// After a collection that didn't free enough to get back under
+	/// `threshold`, multiply `threshold` by `1 / used_space_ratio` instead
+	/// of collecting again right away.
+	pub used_space_ratio: f64,
+	/// Whether whatever is left on the heap at process exit is leaked rather
+	/// than collected. Not read by this crate: nothing here runs a
+	/// collection on exit, whether or not this is set.
+	pub leak_on_drop: bool,
+	/// Emit Valgrind memcheck client requests for every GC block, so a
+	/// leak-checker run under Valgrind reports allocation sites instead of
+	/// just a byte total. Only has an effect when built with the
+	/// `valgrind` feature.
+	#[cfg(feature = "valgrind")]
+	pub valgrind: bool,
+}
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			threshold: 100,
+			used_space_ratio: 0.7,
+			leak_on_drop: true,
+			#[cfg(feature = "valgrind")]
+			valgrind: false,
+		}
+	}
+}
+
+/// Point-in-time collector statistics, as printed by `--gc-print-stats`.
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+	pub collections_performed: usize,
+	pub bytes_allocated: usize,
+}
+
+thread_local!(static CONFIG: Cell<Config> = Cell::new(Config::default()));
+thread_local!(static STATS: RefCell<Stats> = RefCell::new(Stats::default()));
+
+/// Reads or updates the global collector configuration.
+pub fn configure(f: impl FnOnce(&mut Config)) {
+	CONFIG.with(|c| {
+		let mut config = c.get();
+		f(&mut config);
+		c.set(config);
+	});
+}
+
+pub(crate) fn config() -> Config {
+	CONFIG.with(Cell::get)
+}
+
+pub fn stats() -> Stats {
+	STATS.with(|s| *s.borrow())
+}
+
+/// Runs a full mark-and-sweep collection right now, regardless of whether
+/// the allocation threshold was reached.
+pub fn force_collect() {
+	boxed::collect();
+	STATS.with(|s| s.borrow_mut().collections_performed += 1);
+}
+
+/// Called by the collector's allocator every time a new GC block is carved
+/// out of the heap.
+pub(crate) fn note_alloc(addr: *const u8, size: usize) {
+	STATS.with(|s| s.borrow_mut().bytes_allocated += size);
+	#[cfg(feature = "valgrind")]
+	if config().valgrind {
+		valgrind::malloclike_block(addr, size);
+	}
+
+	if stats().bytes_allocated > config().threshold {
+		force_collect();
+		if stats().bytes_allocated > config().threshold {
+			configure(|c| c.threshold = (c.threshold as f64 / c.used_space_ratio) as usize);
+		}
+	}
+}
+
+/// Called by the collector's sweep pass for every block it reclaims.
+pub(crate) fn note_free(addr: *const u8, size: usize) {
+	STATS.with(|s| s.borrow_mut().bytes_allocated -= size);
+	#[cfg(feature = "valgrind")]
+	if config().valgrind {
+		valgrind::freelike_block(addr);
+	}
+}
@@ -0,0 +1,93 @@
+//! Minimal tracing support for the collector: a type implementing [`Trace`]
+//! knows how to reach every `Gc<_>` it (transitively) owns, so the collector
+//! can tell a reachable block from a leaked one.
+pub trait Finalize {
+	fn finalize(&self) {}
+}
+
+/// # Safety
+/// `trace`/`unroot` must visit *every* `Gc<_>` reachable from `self`,
+/// including through nested containers - missing one means the collector
+/// may free a block that's still referenced, which is memory-unsafe.
+pub unsafe trait Trace: Finalize {
+	/// Marks every `Gc<_>` reachable from `self` as reachable.
+	unsafe fn trace(&self);
+	/// Called when `self` becomes an additional independent root (e.g. a
+	/// `Gc<_>` handle is cloned) - increments the root count of every
+	/// `Gc<_>` it contains.
+	fn root(&self);
+	/// The inverse of `root`: called when `self` stops being an independent
+	/// root (typically because it was just moved into a new `GcBox`).
+	unsafe fn unroot(&self);
+}
+
+/// Implements [`Finalize`]/[`Trace`] as no-ops for a leaf type that can
+/// never contain a `Gc<_>`.
+#[macro_export]
+macro_rules! unsafe_empty_trace {
+	($t:ty) => {
+		impl $crate::Finalize for $t {}
+		unsafe impl $crate::Trace for $t {
+			unsafe fn trace(&self) {}
+			fn root(&self) {}
+			unsafe fn unroot(&self) {}
+		}
+	};
+}
+
+unsafe_empty_trace!(());
+unsafe_empty_trace!(bool);
+unsafe_empty_trace!(char);
+unsafe_empty_trace!(i32);
+unsafe_empty_trace!(i64);
+unsafe_empty_trace!(u32);
+unsafe_empty_trace!(u64);
+unsafe_empty_trace!(usize);
+unsafe_empty_trace!(f64);
+unsafe_empty_trace!(String);
+
+impl<T: Trace> Finalize for Option<T> {}
+unsafe impl<T: Trace> Trace for Option<T> {
+	unsafe fn trace(&self) {
+		if let Some(v) = self {
+			unsafe {
+				v.trace();
+			}
+		}
+	}
+	fn root(&self) {
+		if let Some(v) = self {
+			v.root();
+		}
+	}
+	unsafe fn unroot(&self) {
+		if let Some(v) = self {
+			unsafe {
+				v.unroot();
+			}
+		}
+	}
+}
+
+impl<T: Trace> Finalize for Vec<T> {}
+unsafe impl<T: Trace> Trace for Vec<T> {
+	unsafe fn trace(&self) {
+		for v in self {
+			unsafe {
+				v.trace();
+			}
+		}
+	}
+	fn root(&self) {
+		for v in self {
+			v.root();
+		}
+	}
+	unsafe fn unroot(&self) {
+		for v in self {
+			unsafe {
+				v.unroot();
+			}
+		}
+	}
+}
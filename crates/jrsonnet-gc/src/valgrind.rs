@@ -0,0 +1,61 @@
+//! Valgrind memcheck client requests for the GC heap.
+//!
+//! These mirror the `VALGRIND_MALLOCLIKE_BLOCK`/`VALGRIND_FREELIKE_BLOCK`
+//! macros from `valgrind.h`: inline asm that, when the binary isn't running
+//! under Valgrind, the host CPU executes as a no-op (a `rol $3, %rdi; rol
+//! $13, %rdi; rol $61, %rdi; rol $51, %rdi` sequence that nets out to
+//! identity) and Valgrind's JIT instead recognizes and turns into a call
+//! into its tool. That means this module costs nothing when the `valgrind`
+//! feature is off, and nothing at runtime when the feature is on but the
+//! process isn't actually under Valgrind.
+//!
+//! Only the request numbers we need are implemented here; see
+//! `valgrind/valgrind.h` in the Valgrind source tree for the full list.
+const VALGRIND_MALLOCLIKE_BLOCK: usize = 0x1301;
+const VALGRIND_FREELIKE_BLOCK: usize = 0x1302;
+
+#[cfg(all(feature = "valgrind", target_arch = "x86_64"))]
+unsafe fn do_client_request(default: usize, request: usize, args: [usize; 5]) -> usize {
+	let mut result = default;
+	unsafe {
+		std::arch::asm!(
+			"rol $3, %rdi",
+			"rol $13, %rdi",
+			"rol $61, %rdi",
+			"rol $51, %rdi",
+			"xchg %rbx, %rbx",
+			in("rax") &[request, args[0], args[1], args[2], args[3], args[4]] as *const _ as usize,
+			inlateout("rdx") result,
+			// Note: no `preserves_flags` - the `rol` chain does touch CF/OF, same
+			// as upstream `memcheck.h`'s asm block lists `"cc"` in its clobbers.
+			options(att_syntax, nostack),
+		);
+	}
+	result
+}
+
+#[cfg(not(all(feature = "valgrind", target_arch = "x86_64")))]
+unsafe fn do_client_request(default: usize, _request: usize, _args: [usize; 5]) -> usize {
+	default
+}
+
+/// Tells memcheck that `addr..addr + size` was just allocated by our own
+/// allocator, as if by `malloc`, so its leak checker tracks it and flags it
+/// if it's never passed to [`freelike_block`].
+pub fn malloclike_block(addr: *const u8, size: usize) {
+	unsafe {
+		do_client_request(
+			0,
+			VALGRIND_MALLOCLIKE_BLOCK,
+			[addr as usize, size, 0, 0, 0],
+		);
+	}
+}
+
+/// Tells memcheck that the block at `addr` (previously reported via
+/// [`malloclike_block`]) was reclaimed by the collector.
+pub fn freelike_block(addr: *const u8) {
+	unsafe {
+		do_client_request(0, VALGRIND_FREELIKE_BLOCK, [addr as usize, 0, 0, 0, 0]);
+	}
+}
@@ -1,11 +1,32 @@
 use proc_macro2::Span;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, Ident, ItemFn, Pat, PatType};
+use syn::{parse_macro_input, FnArg, GenericArgument, Ident, ItemFn, Pat, PatType, PathArguments, Type};
 
 fn is_location_arg(t: &PatType) -> bool {
 	t.attrs.iter().any(|a| a.path.is_ident("location"))
 }
 
+/// If `ty` is syntactically `Option<T>`, returns `T`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+	let Type::Path(path) = ty else {
+		return None;
+	};
+	let segment = path.path.segments.last()?;
+	if segment.ident != "Option" {
+		return None;
+	}
+	let PathArguments::AngleBracketed(args) = &segment.arguments else {
+		return None;
+	};
+	match args.args.len() {
+		1 => match &args.args[0] {
+			GenericArgument::Type(ty) => Some(ty),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
 #[proc_macro_attribute]
 pub fn builtin(
 	_attr: proc_macro::TokenStream,
@@ -33,8 +54,7 @@ pub fn builtin(
 				Pat::Ident(i) => i.ident.to_string(),
 				_ => panic!("only idents supported yet"),
 			};
-			// TODO: Check if ty == Option<_>
-			let optional = false;
+			let optional = option_inner_type(&t.ty).is_some();
 			quote! {
 				BuiltinParam {
 					name: #ident,
@@ -66,15 +86,27 @@ pub fn builtin(
 					Pat::Ident(i) => i.ident.to_string(),
 					_ => panic!("only idents supported yet"),
 				};
-				let ty = &t.ty;
-				quote! {{
-					let value = parsed.get(#ident).unwrap();
+				if let Some(inner_ty) = option_inner_type(&t.ty) {
+					quote! {{
+						match parsed.get(#ident) {
+							None => None,
+							Some(value) => Some(jrsonnet_evaluator::push_description_frame(
+								|| format!("argument <{}> evaluation", #ident),
+								|| <#inner_ty>::try_from(value.evaluate()?),
+							)?),
+						}
+					}}
+				} else {
+					let ty = &t.ty;
+					quote! {{
+						let value = parsed.get(#ident).unwrap();
 
-					jrsonnet_evaluator::push_description_frame(
-						|| format!("argument <{}> evaluation", #ident),
-						|| <#ty>::try_from(value.evaluate()?),
-					)?
-				}}
+						jrsonnet_evaluator::push_description_frame(
+							|| format!("argument <{}> evaluation", #ident),
+							|| <#ty>::try_from(value.evaluate()?),
+						)?
+					}}
+				}
 			}
 		}).collect::<Vec<_>>();
 	
@@ -0,0 +1,234 @@
+//! Compares parse (and parse+walk) allocation counts and wall time for a
+//! deeply nested object between the default arena backend and the
+//! `rc-backend` feature.
+//!
+//! Run with `cargo bench --bench arena_alloc` and, for the `Rc` baseline,
+//! `cargo bench --bench arena_alloc --features rc-backend`.
+//!
+//! There's no evaluator in this tree to measure a real "eval" pass against
+//! (no `jrsonnet-evaluator` crate exists here), so the "walk" benchmarks
+//! below are a stand-in: a plain post-order traversal over the `Expr`
+//! tree the parse produced (see [`walk`] for the parts it skips), counting
+//! allocations along the way. That's
+//! honest about not being real Jsonnet evaluation (no thunks, no
+//! self/super resolution, no object field materialization) while still
+//! answering the allocation-count question for the one thing this crate
+//! actually does after parsing: walking the tree it built.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use jsonnet_parser::{Arena, CompSpec, Expr, LocExpr, ObjBody};
+
+/// Wraps [`System`], counting every allocation so benchmarks can report an
+/// allocation count rather than only wall-clock time.
+struct CountingAllocator;
+
+static ALLOC_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOC_COUNT.fetch_add(1, Ordering::Relaxed);
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		unsafe { System.dealloc(ptr, layout) }
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		ALLOC_COUNT.fetch_add(1, Ordering::Relaxed);
+		unsafe { System.realloc(ptr, layout, new_size) }
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn reset_alloc_count() {
+	ALLOC_COUNT.store(0, Ordering::Relaxed);
+}
+
+fn alloc_count() -> usize {
+	ALLOC_COUNT.load(Ordering::Relaxed)
+}
+
+/// `{a: {a: {a: ... 1 ... }}}`, nested `depth` times.
+fn nested_object_source(depth: usize) -> String {
+	let mut out = String::new();
+	for _ in 0..depth {
+		out.push_str("{ a: ");
+	}
+	out.push('1');
+	for _ in 0..depth {
+		out.push('}');
+	}
+	out
+}
+
+/// Stand-in for "eval": a plain post-order walk over the tree the parse
+/// produced, summing the `Num` literals it finds. It does not descend into
+/// `if` conditions, assertions, computed field names, parameter defaults,
+/// the first `for` of an array comprehension, or the locals and specs of an
+/// object comprehension. See the module doc comment for why this isn't real
+/// Jsonnet evaluation.
+fn walk(expr: &LocExpr<'_>) -> f64 {
+	walk_expr(&expr.0)
+}
+
+fn walk_expr(expr: &Expr<'_>) -> f64 {
+	match expr {
+		Expr::Num(n) => *n,
+		Expr::Arr(items) => items.iter().map(walk).sum(),
+		Expr::ArrComp(e, _, rest) => walk(e) + walk_comp_specs(rest),
+		Expr::Obj(body) | Expr::ObjExtend(_, body) => walk_obj_body(body),
+		Expr::Parened(e) => walk(e),
+		Expr::UnaryOp(_, e) => walk(e),
+		Expr::BinaryOp(l, _, r) => walk(l) + walk(r),
+		Expr::AssertExpr(_, body) => walk(body),
+		Expr::LocalExpr(binds, body) => {
+			binds.iter().map(|b| walk(&b.value)).sum::<f64>() + walk(body)
+		}
+		Expr::Error(e) => walk(e),
+		Expr::Apply(callee, args) => {
+			walk(callee) + args.0.iter().map(|a| walk(&a.1)).sum::<f64>()
+		}
+		Expr::Select(e, _) => walk(e),
+		Expr::Index(e, i) => walk(e) + walk(i),
+		Expr::Slice(e, slice) => {
+			walk(e)
+				+ slice.start.as_ref().map(walk).unwrap_or(0.0)
+				+ slice.end.as_ref().map(walk).unwrap_or(0.0)
+				+ slice.step.as_ref().map(walk).unwrap_or(0.0)
+		}
+		Expr::Function(_, body) => walk(body),
+		Expr::IfElse {
+			cond_then,
+			cond_else,
+			..
+		} => walk(cond_then) + cond_else.as_ref().map(walk).unwrap_or(0.0),
+		_ => 0.0,
+	}
+}
+
+fn walk_obj_body(body: &ObjBody<'_>) -> f64 {
+	match body {
+		ObjBody::MemberList(members) => members
+			.iter()
+			.filter_map(|m| match m {
+				jsonnet_parser::Member::Field(f) => Some(walk(&f.value)),
+				jsonnet_parser::Member::BindStmt(b) => Some(walk(&b.value)),
+				jsonnet_parser::Member::AssertStmt(_) => None,
+			})
+			.sum(),
+		ObjBody::ObjComp { key, value, .. } => walk(key) + walk(value),
+	}
+}
+
+fn walk_comp_specs(specs: &[CompSpec<'_>]) -> f64 {
+	specs
+		.iter()
+		.map(|s| match s {
+			CompSpec::IfSpec(i) => walk(&i.0),
+			CompSpec::ForSpec(f) => walk(&f.1),
+		})
+		.sum()
+}
+
+fn bench_parse(c: &mut Criterion) {
+	let source = nested_object_source(2000);
+
+	c.bench_function("parse nested object", |b| {
+		b.iter(|| {
+			#[cfg(not(feature = "rc-backend"))]
+			{
+				let arena = Arena::new();
+				black_box(jsonnet_parser::parse(
+					black_box(&source),
+					&Default::default(),
+					&arena,
+				));
+			}
+			#[cfg(feature = "rc-backend")]
+			{
+				black_box(jsonnet_parser::parse(black_box(&source), &Default::default()));
+			}
+		});
+	});
+}
+
+/// Reports allocation *count* rather than wall time: Criterion only speaks
+/// `Duration`, so we smuggle the count through as nanoseconds, matching the
+/// common trick used to bolt non-timing metrics onto `iter_custom`. The
+/// printed "time" in the Criterion report for this benchmark is actually an
+/// allocation count, not a duration - see the benchmark name.
+fn bench_parse_allocs(c: &mut Criterion) {
+	let source = nested_object_source(2000);
+
+	c.bench_function("parse nested object (allocation count, as ns)", |b| {
+		b.iter_custom(|iters| {
+			let mut total = 0u64;
+			for _ in 0..iters {
+				reset_alloc_count();
+				#[cfg(not(feature = "rc-backend"))]
+				{
+					let arena = Arena::new();
+					black_box(jsonnet_parser::parse(
+						black_box(&source),
+						&Default::default(),
+						&arena,
+					));
+				}
+				#[cfg(feature = "rc-backend")]
+				{
+					black_box(jsonnet_parser::parse(black_box(&source), &Default::default()));
+				}
+				total += alloc_count() as u64;
+			}
+			Duration::from_nanos(total)
+		});
+	});
+}
+
+/// Same as [`bench_parse_allocs`], but also walks the resulting tree (see
+/// the module doc comment for why that's the closest stand-in for "eval"
+/// available in this tree).
+fn bench_parse_and_walk_allocs(c: &mut Criterion) {
+	let source = nested_object_source(2000);
+
+	c.bench_function(
+		"parse + walk nested object (allocation count, as ns)",
+		|b| {
+			b.iter_custom(|iters| {
+				let mut total = 0u64;
+				for _ in 0..iters {
+					reset_alloc_count();
+					#[cfg(not(feature = "rc-backend"))]
+					{
+						let arena = Arena::new();
+						let parsed = jsonnet_parser::parse(&source, &Default::default(), &arena)
+							.expect("nested_object_source produces valid Jsonnet");
+						black_box(walk(black_box(&parsed)));
+					}
+					#[cfg(feature = "rc-backend")]
+					{
+						let parsed = jsonnet_parser::parse(&source, &Default::default())
+							.expect("nested_object_source produces valid Jsonnet");
+						black_box(walk(black_box(&parsed)));
+					}
+					total += alloc_count() as u64;
+				}
+				Duration::from_nanos(total)
+			});
+		},
+	);
+}
+
+criterion_group!(
+	benches,
+	bench_parse,
+	bench_parse_allocs,
+	bench_parse_and_walk_allocs
+);
+criterion_main!(benches);
@@ -1,11 +1,16 @@
-use std::{fmt::Debug, rc::Rc};
+use std::fmt::Debug;
+#[cfg(feature = "rc-backend")]
+use std::rc::Rc;
+
+pub mod arena;
+pub use arena::Arena;
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum FieldName {
+pub enum FieldName<'a> {
 	/// {fixed: 2}
 	Fixed(String),
 	/// {["dyn"+"amic"]: 3}
-	Dyn(LocExpr),
+	Dyn(LocExpr<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,22 +24,22 @@ pub enum Visibility {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct AssertStmt(pub LocExpr, pub Option<LocExpr>);
+pub struct AssertStmt<'a>(pub LocExpr<'a>, pub Option<LocExpr<'a>>);
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct FieldMember {
-	pub name: FieldName,
+pub struct FieldMember<'a> {
+	pub name: FieldName<'a>,
 	pub plus: bool,
-	pub params: Option<ParamsDesc>,
+	pub params: Option<ParamsDesc<'a>>,
 	pub visibility: Visibility,
-	pub value: LocExpr,
+	pub value: LocExpr<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Member {
-	Field(FieldMember),
-	BindStmt(BindSpec),
-	AssertStmt(AssertStmt),
+pub enum Member<'a> {
+	Field(FieldMember<'a>),
+	BindStmt(BindSpec<'a>),
+	AssertStmt(AssertStmt<'a>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -77,49 +82,49 @@ pub enum BinaryOpType {
 
 /// name, default value
 #[derive(Debug, Clone, PartialEq)]
-pub struct Param(pub String, pub Option<LocExpr>);
+pub struct Param<'a>(pub String, pub Option<LocExpr<'a>>);
 /// Defined function parameters
 #[derive(Debug, Clone, PartialEq)]
-pub struct ParamsDesc(pub Vec<Param>);
-impl ParamsDesc {
-	pub fn with_defaults(&self) -> Vec<Param> {
+pub struct ParamsDesc<'a>(pub Vec<Param<'a>>);
+impl<'a> ParamsDesc<'a> {
+	pub fn with_defaults(&self) -> Vec<Param<'a>> {
 		self.0.iter().filter(|e| e.1.is_some()).cloned().collect()
 	}
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Arg(pub Option<String>, pub LocExpr);
+pub struct Arg<'a>(pub Option<String>, pub LocExpr<'a>);
 #[derive(Debug, Clone, PartialEq)]
-pub struct ArgsDesc(pub Vec<Arg>);
+pub struct ArgsDesc<'a>(pub Vec<Arg<'a>>);
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct BindSpec {
+pub struct BindSpec<'a> {
 	pub name: String,
-	pub params: Option<ParamsDesc>,
-	pub value: LocExpr,
+	pub params: Option<ParamsDesc<'a>>,
+	pub value: LocExpr<'a>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct IfSpecData(pub LocExpr);
+pub struct IfSpecData<'a>(pub LocExpr<'a>);
 #[derive(Debug, Clone, PartialEq)]
-pub struct ForSpecData(pub String, pub LocExpr);
+pub struct ForSpecData<'a>(pub String, pub LocExpr<'a>);
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum CompSpec {
-	IfSpec(IfSpecData),
-	ForSpec(ForSpecData),
+pub enum CompSpec<'a> {
+	IfSpec(IfSpecData<'a>),
+	ForSpec(ForSpecData<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum ObjBody {
-	MemberList(Vec<Member>),
+pub enum ObjBody<'a> {
+	MemberList(Vec<Member<'a>>),
 	ObjComp {
-		pre_locals: Vec<BindSpec>,
-		key: LocExpr,
-		value: LocExpr,
-		post_locals: Vec<BindSpec>,
-		first: ForSpecData,
-		rest: Vec<CompSpec>,
+		pre_locals: Vec<BindSpec<'a>>,
+		key: LocExpr<'a>,
+		value: LocExpr<'a>,
+		post_locals: Vec<BindSpec<'a>>,
+		first: ForSpecData<'a>,
+		rest: Vec<CompSpec<'a>>,
 	},
 }
 
@@ -134,15 +139,15 @@ pub enum LiteralType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct SliceDesc {
-	pub start: Option<LocExpr>,
-	pub end: Option<LocExpr>,
-	pub step: Option<LocExpr>,
+pub struct SliceDesc<'a> {
+	pub start: Option<LocExpr<'a>>,
+	pub end: Option<LocExpr<'a>>,
+	pub step: Option<LocExpr<'a>>,
 }
 
 /// Syntax base
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expr {
+pub enum Expr<'a> {
 	Literal(LiteralType),
 
 	/// String value: "hello"
@@ -153,7 +158,7 @@ pub enum Expr {
 	Var(String),
 
 	/// Array of expressions: [1, 2, "Hello"]
-	Arr(Vec<LocExpr>),
+	Arr(Vec<LocExpr<'a>>),
 	/// Array comprehension:
 	/// ```jsonnet
 	///  ingredients: [
@@ -165,60 +170,60 @@ pub enum Expr {
 	///    ]
 	///  ],
 	/// ```
-	ArrComp(LocExpr, ForSpecData, Vec<CompSpec>),
+	ArrComp(LocExpr<'a>, ForSpecData<'a>, Vec<CompSpec<'a>>),
 
 	/// Object: {a: 2}
-	Obj(ObjBody),
+	Obj(ObjBody<'a>),
 	/// Object extension: var1 {b: 2}
-	ObjExtend(LocExpr, ObjBody),
+	ObjExtend(LocExpr<'a>, ObjBody<'a>),
 
 	/// (obj)
-	Parened(LocExpr),
+	Parened(LocExpr<'a>),
 
 	/// Params in function definition
 	/// hello, world, test = 2
-	Params(ParamsDesc),
+	Params(ParamsDesc<'a>),
 	/// Args in function call
 	/// 2 + 2, 3, named = 6
-	Args(ArgsDesc),
+	Args(ArgsDesc<'a>),
 
 	/// -2
-	UnaryOp(UnaryOpType, LocExpr),
+	UnaryOp(UnaryOpType, LocExpr<'a>),
 	/// 2 - 2
-	BinaryOp(LocExpr, BinaryOpType, LocExpr),
+	BinaryOp(LocExpr<'a>, BinaryOpType, LocExpr<'a>),
 	/// assert 2 == 2 : "Math is broken"
-	AssertExpr(AssertStmt, LocExpr),
+	AssertExpr(AssertStmt<'a>, LocExpr<'a>),
 	/// local a = 2; { b: a }
-	LocalExpr(Vec<BindSpec>, LocExpr),
+	LocalExpr(Vec<BindSpec<'a>>, LocExpr<'a>),
 
 	/// a = 3
-	Bind(BindSpec),
+	Bind(BindSpec<'a>),
 	/// import "hello"
 	Import(String),
 	/// importStr "file.txt"
 	ImportStr(String),
 	/// error "I'm broken"
-	Error(LocExpr),
+	Error(LocExpr<'a>),
 	/// a(b, c)
-	Apply(LocExpr, ArgsDesc),
+	Apply(LocExpr<'a>, ArgsDesc<'a>),
 	///
-	Select(LocExpr, String),
+	Select(LocExpr<'a>, String),
 	/// a[b]
-	Index(LocExpr, LocExpr),
+	Index(LocExpr<'a>, LocExpr<'a>),
 	/// a[1::2]
-	Slice(LocExpr, SliceDesc),
+	Slice(LocExpr<'a>, SliceDesc<'a>),
 	/// function(x) x
-	Function(ParamsDesc, LocExpr),
+	Function(ParamsDesc<'a>, LocExpr<'a>),
 	/// if true == false then 1 else 2
 	IfElse {
-		cond: IfSpecData,
-		cond_then: LocExpr,
-		cond_else: Option<LocExpr>,
+		cond: IfSpecData<'a>,
+		cond_then: LocExpr<'a>,
+		cond_else: Option<LocExpr<'a>>,
 	},
 	/// if 2 = 3
-	IfSpec(IfSpecData),
+	IfSpec(IfSpecData<'a>),
 	/// for elem in array
-	ForSpec(ForSpecData),
+	ForSpec(ForSpecData<'a>),
 }
 
 /// file, begin offset, end offset
@@ -230,16 +235,36 @@ impl Debug for ExprLocation {
 	}
 }
 
+/// The owning pointer type `LocExpr`/`Expr` nodes are stored behind.
+///
+/// Normally this is `&'a Expr<'a>`, handed out by [`Arena`]: a whole parsed
+/// file lives in a couple of bump-allocated chunks, and walking the tree is
+/// just pointer dereferences. The `rc-backend` feature switches this back to
+/// `Rc<Expr<'a>>` (with `'a` instantiated to `'static`), which is slower to
+/// parse but lets individual nodes outlive the file they came from - this is
+/// what the incremental evaluator (which keeps old ASTs around across
+/// re-evaluations) currently relies on.
+#[cfg(feature = "rc-backend")]
+pub type ExprPtr<'a> = Rc<Expr<'a>>;
+#[cfg(not(feature = "rc-backend"))]
+pub type ExprPtr<'a> = &'a Expr<'a>;
+
+#[cfg(feature = "rc-backend")]
+pub type LocPtr<'a> = Rc<ExprLocation>;
+#[cfg(not(feature = "rc-backend"))]
+pub type LocPtr<'a> = &'a ExprLocation;
+
 /// Holds AST expression and its location in source file+
 #[derive(Clone, PartialEq)]
-pub struct LocExpr(pub Rc<Expr>, pub Option<Rc<ExprLocation>>);
-impl Debug for LocExpr {
+pub struct LocExpr<'a>(pub ExprPtr<'a>, pub Option<LocPtr<'a>>);
+impl<'a> Debug for LocExpr<'a> {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		write!(f, "{:?} from {:?}", self.0, self.1)
 	}
 }
 
 /// Creates LocExpr from Expr and ExprLocation components
+#[cfg(feature = "rc-backend")]
 #[macro_export]
 macro_rules! loc_expr {
 	($expr:expr, $need_loc:expr, ($name:expr, $start:expr, $end:expr)) => {
@@ -258,10 +283,37 @@ macro_rules! loc_expr {
 	};
 }
 
+/// Creates LocExpr from Expr and ExprLocation components, allocating both
+/// into `$arena`
+#[cfg(not(feature = "rc-backend"))]
+#[macro_export]
+macro_rules! loc_expr {
+	($arena:expr, $expr:expr, $need_loc:expr, ($name:expr, $start:expr, $end:expr)) => {
+		LocExpr(
+			$arena.alloc_expr(|| $expr),
+			if $need_loc {
+				Some($arena.alloc_location(|| ExprLocation($name.to_owned(), $start, $end)))
+			} else {
+				None
+			},
+		)
+	};
+}
+
 /// Creates LocExpr without location info
+#[cfg(feature = "rc-backend")]
 #[macro_export]
 macro_rules! loc_expr_todo {
 	($expr:expr) => {
 		LocExpr(Rc::new($expr), None)
 	};
 }
+
+/// Creates LocExpr without location info, allocating into `$arena`
+#[cfg(not(feature = "rc-backend"))]
+#[macro_export]
+macro_rules! loc_expr_todo {
+	($arena:expr, $expr:expr) => {
+		LocExpr($arena.alloc_expr(|| $expr), None)
+	};
+}
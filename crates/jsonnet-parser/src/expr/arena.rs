@@ -0,0 +1,187 @@
+//! A bump arena for [`Expr`]/[`ExprLocation`] nodes.
+//!
+//! Parsing a file used to wrap every node in its own `Rc`, so a deeply nested
+//! manifest produced thousands of tiny allocations (plus a refcount bump per
+//! clone while walking the tree). `Arena` instead owns every node produced
+//! while parsing a single file in a handful of large chunks, and is dropped
+//! as a whole once evaluation of that file is done - no per-node `free`.
+use std::cell::RefCell;
+use std::mem::MaybeUninit;
+
+use super::{Expr, ExprLocation};
+
+/// Number of elements in the first chunk of a [`TypedArena`], doubled on
+/// every subsequent chunk.
+const FIRST_CHUNK_LEN: usize = 64;
+
+/// Bump allocator for a single `T`. Chunks are never moved nor grown in
+/// place, so a reference handed out by [`TypedArena::alloc`] stays valid for
+/// as long as the arena is alive, even while more elements are allocated.
+struct TypedArena<T> {
+	chunks: RefCell<Vec<Vec<MaybeUninit<T>>>>,
+}
+
+impl<T> TypedArena<T> {
+	fn new() -> Self {
+		Self {
+			chunks: RefCell::new(vec![Vec::with_capacity(FIRST_CHUNK_LEN)]),
+		}
+	}
+
+	/// Allocates `f()` in the arena, returning a reference tied to this
+	/// particular `&'a self` borrow. `f` is called exactly once, after room
+	/// for the new element has been made.
+	fn alloc<'a>(&'a self, f: impl FnOnce() -> T) -> &'a T {
+		let mut chunks = self.chunks.borrow_mut();
+		let last = chunks.last_mut().expect("arena always has a chunk");
+		if last.len() == last.capacity() {
+			let next_cap = last.capacity() * 2;
+			chunks.push(Vec::with_capacity(next_cap));
+		}
+		let last = chunks.last_mut().expect("arena always has a chunk");
+		let idx = last.len();
+		last.push(MaybeUninit::new(f()));
+		// SAFETY: chunks are allocated with a fixed capacity and never
+		// reallocated (we push a brand new `Vec` instead of growing one that
+		// already handed out references), so this pointer stays valid for
+		// the lifetime of `self`, and we never hand out a mutable alias to
+		// the same slot again.
+		unsafe { &*last[idx].as_ptr() }
+	}
+}
+
+impl<T> Drop for TypedArena<T> {
+	fn drop(&mut self) {
+		// Elements are `MaybeUninit`, so dropping the `Vec`s wouldn't run
+		// `T`'s destructor on its own - do it by hand for every slot we
+		// actually initialized.
+		for chunk in self.chunks.get_mut() {
+			for slot in chunk.iter_mut() {
+				// SAFETY: every slot with index `< len` was initialized by
+				// `alloc` above.
+				unsafe { slot.assume_init_drop() };
+			}
+		}
+	}
+}
+
+/// Owns every [`Expr`] and [`ExprLocation`] node parsed out of a single
+/// file.
+///
+/// Deliberately *not* generic over the lifetime it hands out (unlike a
+/// first attempt at this might look): an `Arena<'a>` would need a `&'a self`
+/// borrow to produce that very `'a`, which no owned `Arena` value can ever
+/// satisfy - the classic self-referential-arena trap. Instead, `Arena` is
+/// plain, and each `alloc_*` call is generic over its own lifetime, elided
+/// from the `&self` borrow at the call site, exactly like
+/// [`TypedArena::alloc`] above and like `bumpalo::Bump::alloc`.
+pub struct Arena {
+	exprs: TypedArena<Expr<'static>>,
+	locations: TypedArena<ExprLocation>,
+}
+
+impl Arena {
+	pub fn new() -> Self {
+		Self {
+			exprs: TypedArena::new(),
+			locations: TypedArena::new(),
+		}
+	}
+
+	/// Allocates an [`Expr`] node, returning a reference valid for as long as
+	/// this arena is alive.
+	pub fn alloc_expr<'a>(&'a self, f: impl FnOnce() -> Expr<'a>) -> &'a Expr<'a> {
+		// SAFETY: `Expr<'a>` has the same layout (and drop glue) for every
+		// `'a` - the lifetime only ever appears behind the shared references
+		// inside `LocExpr`, never affecting size/alignment. We erase it to
+		// `'static` so every node can live in one `TypedArena` regardless of
+		// which call's `'a` produced it, then restore the caller's own `'a`
+		// on the way out; that's sound because the reference we hand back
+		// still borrows `self` for exactly `'a`, so it can't outlive the
+		// data it points to.
+		unsafe {
+			let value: Expr<'static> = std::mem::transmute(f());
+			let stored: &'a Expr<'static> = self.exprs.alloc(|| value);
+			std::mem::transmute(stored)
+		}
+	}
+
+	/// Allocates an [`ExprLocation`] node. Kept separate from
+	/// `alloc_expr` because locations are requested far less often (only
+	/// when `--keep-locations`-style diagnostics are enabled) and are a
+	/// different, smaller type.
+	pub fn alloc_location<'a>(&'a self, f: impl FnOnce() -> ExprLocation) -> &'a ExprLocation {
+		self.locations.alloc(f)
+	}
+}
+
+impl Default for Arena {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::Cell;
+	use std::rc::Rc;
+
+	struct DropTracker(Rc<Cell<usize>>);
+	impl Drop for DropTracker {
+		fn drop(&mut self) {
+			self.0.set(self.0.get() + 1);
+		}
+	}
+
+	#[test]
+	fn typed_arena_grows_across_chunks_without_moving_elements() {
+		let arena: TypedArena<usize> = TypedArena::new();
+		let mut refs = Vec::new();
+		// FIRST_CHUNK_LEN doubles each chunk, so this forces a few growths.
+		for i in 0..(FIRST_CHUNK_LEN * 3 + 1) {
+			refs.push(arena.alloc(|| i));
+		}
+		assert!(
+			arena.chunks.borrow().len() > 1,
+			"should have grown past the first chunk"
+		);
+		for (i, r) in refs.iter().enumerate() {
+			assert_eq!(**r, i, "growing later chunks must not move earlier elements");
+		}
+	}
+
+	#[test]
+	fn typed_arena_drops_every_element_on_drop() {
+		let counter = Rc::new(Cell::new(0));
+		{
+			let arena: TypedArena<DropTracker> = TypedArena::new();
+			for _ in 0..(FIRST_CHUNK_LEN + 5) {
+				arena.alloc(|| DropTracker(counter.clone()));
+			}
+			assert_eq!(counter.get(), 0, "nothing drops while the arena is alive");
+		}
+		assert_eq!(
+			counter.get(),
+			FIRST_CHUNK_LEN + 5,
+			"dropping the arena must drop every element, across every chunk"
+		);
+	}
+
+	#[test]
+	fn arena_alloc_expr_erases_and_restores_the_caller_lifetime() {
+		let arena = Arena::new();
+		let a = arena.alloc_expr(|| Expr::Num(1.0));
+		let b = arena.alloc_expr(|| Expr::Num(2.0));
+		assert_eq!(*a, Expr::Num(1.0));
+		assert_eq!(*b, Expr::Num(2.0));
+	}
+
+	#[test]
+	fn arena_alloc_location_works_independently_of_expr_chunks() {
+		let arena = Arena::new();
+		let loc = arena.alloc_location(|| ExprLocation("test.jsonnet".to_owned(), 0, 3));
+		assert_eq!(loc.1, 0);
+		assert_eq!(loc.2, 3);
+	}
+}
@@ -0,0 +1,5 @@
+mod expr;
+mod parser;
+
+pub use expr::*;
+pub use parser::*;
@@ -0,0 +1,1072 @@
+//! Recursive-descent parser wiring the [`Arena`]-backed `Expr`/`LocExpr`
+//! (see `expr.rs`) into real parsing. Covers the bulk of the expression
+//! grammar: literals, objects (including quoted/computed keys, `+:`,
+//! `::`/`:::` visibility, object-level `local`/`assert`), arrays and array
+//! comprehensions, unary/binary operators (precedence-climbing, matching
+//! Jsonnet's operator precedence table), `.`/`[]`/`()`/object-extend
+//! postfix chains, slicing, `if`/`then`/`else`, `local ... ;`,
+//! `assert ... ;`, `function(...) ...`, `import`/`importstr`, `error`, and
+//! `//`/`#`/`/* */` comments.
+//!
+//! Still missing relative to the full grammar: object comprehensions
+//! (`{[k]: v for x in xs}` - only array comprehensions are wired up),
+//! the `tailstrict` call suffix, verbatim/triple-quoted string literals, and
+//! the `\uXXXX`, `\b` and `\f` string escapes.
+use crate::{
+	Arg, ArgsDesc, AssertStmt, BinaryOpType, BindSpec, CompSpec, Expr, FieldMember, FieldName,
+	ForSpecData, IfSpecData, LiteralType, LocExpr, Member, ObjBody, Param, ParamsDesc, SliceDesc,
+	UnaryOpType, Visibility,
+};
+#[cfg(not(feature = "rc-backend"))]
+use crate::Arena;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError(pub String);
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// Per-parse-call options; `file_name` is threaded into every
+/// [`crate::ExprLocation`] when `loc_data` is set.
+#[derive(Debug, Clone, Default)]
+pub struct ParserSettings {
+	pub file_name: String,
+	pub loc_data: bool,
+}
+
+// `Expr<'a>`/`LocExpr<'a>` already abstract over the two storage backends
+// (see `expr.rs`): under `rc-backend` the `'a` parameter is just unused
+// phantom data, since `Rc` doesn't borrow anything, so it works for any
+// lifetime the caller picks (here, `'static`). Under the default arena
+// backend it's the arena's own borrow.
+
+#[cfg(feature = "rc-backend")]
+pub fn parse(code: &str, settings: &ParserSettings) -> Result<LocExpr<'static>> {
+	let mut parser: Parser<'_, 'static> = Parser {
+		code: code.as_bytes(),
+		pos: 0,
+		settings,
+		_arena: std::marker::PhantomData,
+	};
+	let expr = parser.parse_expr()?;
+	parser.skip_ws();
+	parser.expect_eof()?;
+	Ok(expr)
+}
+
+#[cfg(not(feature = "rc-backend"))]
+pub fn parse<'a>(code: &str, settings: &ParserSettings, arena: &'a Arena) -> Result<LocExpr<'a>> {
+	let mut parser = Parser {
+		code: code.as_bytes(),
+		pos: 0,
+		settings,
+		arena,
+	};
+	let expr = parser.parse_expr()?;
+	parser.skip_ws();
+	parser.expect_eof()?;
+	Ok(expr)
+}
+
+struct Parser<'s, 'a> {
+	code: &'s [u8],
+	pos: usize,
+	settings: &'s ParserSettings,
+	#[cfg(not(feature = "rc-backend"))]
+	arena: &'a Arena,
+	#[cfg(feature = "rc-backend")]
+	_arena: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'s, 'a> Parser<'s, 'a> {
+	#[cfg(not(feature = "rc-backend"))]
+	fn mk(&self, expr: Expr<'a>, start: usize, end: usize) -> LocExpr<'a> {
+		loc_expr!(
+			self.arena,
+			expr,
+			self.settings.loc_data,
+			(self.settings.file_name, start, end)
+		)
+	}
+	#[cfg(feature = "rc-backend")]
+	fn mk(&self, expr: Expr<'a>, start: usize, end: usize) -> LocExpr<'a> {
+		loc_expr!(
+			expr,
+			self.settings.loc_data,
+			(self.settings.file_name, start, end)
+		)
+	}
+
+	fn skip_ws(&mut self) {
+		loop {
+			while self.pos < self.code.len() && (self.code[self.pos] as char).is_ascii_whitespace()
+			{
+				self.pos += 1;
+			}
+			if self.code[self.pos..].starts_with(b"//") || self.code[self.pos..].starts_with(b"#") {
+				while self.pos < self.code.len() && self.code[self.pos] != b'\n' {
+					self.pos += 1;
+				}
+				continue;
+			}
+			if self.code[self.pos..].starts_with(b"/*") {
+				self.pos += 2;
+				while self.pos < self.code.len() && !self.code[self.pos..].starts_with(b"*/") {
+					self.pos += 1;
+				}
+				self.pos = (self.pos + 2).min(self.code.len());
+				continue;
+			}
+			break;
+		}
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.code.get(self.pos).copied()
+	}
+
+	fn expect_byte(&mut self, b: u8) -> Result<()> {
+		self.skip_ws();
+		if self.peek() == Some(b) {
+			self.pos += 1;
+			Ok(())
+		} else {
+			Err(ParseError(format!(
+				"expected '{}' at offset {}",
+				b as char, self.pos
+			)))
+		}
+	}
+
+	fn expect_eof(&self) -> Result<()> {
+		if self.pos == self.code.len() {
+			Ok(())
+		} else {
+			Err(ParseError(format!(
+				"unexpected trailing input at offset {}",
+				self.pos
+			)))
+		}
+	}
+
+	/// Looks ahead for an identifier-shaped token without consuming it -
+	/// used to decide between a keyword form (`local`, `if`, ...) and a
+	/// plain binary-op expression before committing to either.
+	fn peek_ident(&self) -> Option<String> {
+		let mut p = self.pos;
+		while p < self.code.len() && (self.code[p] as char).is_ascii_whitespace() {
+			p += 1;
+		}
+		let start = p;
+		while p < self.code.len()
+			&& ((self.code[p] as char).is_ascii_alphanumeric() || self.code[p] == b'_')
+		{
+			p += 1;
+		}
+		if p == start {
+			None
+		} else {
+			Some(String::from_utf8_lossy(&self.code[start..p]).into_owned())
+		}
+	}
+
+	/// Whether `kw` appears at the current position as a whole word (not a
+	/// prefix of a longer identifier). Does not consume.
+	fn peek_keyword(&self, kw: &str) -> bool {
+		let bytes = kw.as_bytes();
+		if !self.code[self.pos..].starts_with(bytes) {
+			return false;
+		}
+		let after = self.pos + bytes.len();
+		!self
+			.code
+			.get(after)
+			.map(|b| (*b as char).is_ascii_alphanumeric() || *b == b'_')
+			.unwrap_or(false)
+	}
+
+	/// If `kw` is next (as a whole word), consumes it and returns `true`.
+	fn eat_keyword(&mut self, kw: &str) -> bool {
+		self.skip_ws();
+		if self.peek_keyword(kw) {
+			self.pos += kw.len();
+			true
+		} else {
+			false
+		}
+	}
+
+	fn expect_keyword(&mut self, kw: &str) -> Result<()> {
+		if self.eat_keyword(kw) {
+			Ok(())
+		} else {
+			Err(ParseError(format!(
+				"expected '{}' at offset {}",
+				kw, self.pos
+			)))
+		}
+	}
+
+	fn parse_ident(&mut self) -> Result<String> {
+		self.skip_ws();
+		let start = self.pos;
+		while self
+			.peek()
+			.map(|b| (b as char).is_ascii_alphanumeric() || b == b'_')
+			.unwrap_or(false)
+		{
+			self.pos += 1;
+		}
+		if self.pos == start {
+			return Err(ParseError(format!(
+				"expected identifier at offset {}",
+				start
+			)));
+		}
+		Ok(String::from_utf8_lossy(&self.code[start..self.pos]).into_owned())
+	}
+
+	/// Digits, optional fractional part, optional exponent - no sign: a
+	/// leading `-`/`+` is parsed as a [`UnaryOpType`] by
+	/// [`Self::parse_unary`], not folded into the literal.
+	fn parse_number(&mut self) -> Result<f64> {
+		let start = self.pos;
+		let is_digit = |b: u8| (b as char).is_ascii_digit();
+		while self.peek().map(is_digit).unwrap_or(false) {
+			self.pos += 1;
+		}
+		if self.peek() == Some(b'.') {
+			self.pos += 1;
+			while self.peek().map(is_digit).unwrap_or(false) {
+				self.pos += 1;
+			}
+		}
+		if matches!(self.peek(), Some(b'e') | Some(b'E')) {
+			self.pos += 1;
+			if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+				self.pos += 1;
+			}
+			while self.peek().map(is_digit).unwrap_or(false) {
+				self.pos += 1;
+			}
+		}
+		String::from_utf8_lossy(&self.code[start..self.pos])
+			.parse()
+			.map_err(|_| ParseError(format!("invalid number at offset {}", start)))
+	}
+
+	fn parse_string(&mut self) -> Result<String> {
+		self.skip_ws();
+		let quote = match self.peek() {
+			Some(b @ b'"') | Some(b @ b'\'') => b,
+			_ => return Err(ParseError(format!("expected string at offset {}", self.pos))),
+		};
+		self.pos += 1;
+		let mut out = String::new();
+		loop {
+			match self.peek() {
+				None => return Err(ParseError("unterminated string".into())),
+				Some(b) if b == quote => {
+					self.pos += 1;
+					break;
+				}
+				Some(b'\\') => {
+					self.pos += 1;
+					match self.peek() {
+						Some(b'"') => out.push('"'),
+						Some(b'\'') => out.push('\''),
+						Some(b'\\') => out.push('\\'),
+						Some(b'/') => out.push('/'),
+						Some(b'n') => out.push('\n'),
+						Some(b't') => out.push('\t'),
+						Some(b'r') => out.push('\r'),
+						other => {
+							return Err(ParseError(format!(
+								"unsupported escape {:?} at offset {}",
+								other, self.pos
+							)))
+						}
+					}
+					self.pos += 1;
+				}
+				Some(_) => {
+					let start = self.pos;
+					self.pos += 1;
+					out.push_str(&String::from_utf8_lossy(&self.code[start..self.pos]));
+				}
+			}
+		}
+		Ok(out)
+	}
+
+	fn parse_field_name(&mut self) -> Result<FieldName<'a>> {
+		self.skip_ws();
+		match self.peek() {
+			Some(b'"') | Some(b'\'') => Ok(FieldName::Fixed(self.parse_string()?)),
+			Some(b'[') => {
+				self.pos += 1;
+				let key = self.parse_expr()?;
+				self.expect_byte(b']')?;
+				Ok(FieldName::Dyn(key))
+			}
+			Some(b) if (b as char).is_ascii_alphabetic() || b == b'_' => {
+				Ok(FieldName::Fixed(self.parse_ident()?))
+			}
+			_ => Err(ParseError(format!(
+				"expected field name at offset {}",
+				self.pos
+			))),
+		}
+	}
+
+	fn parse_visibility(&mut self) -> Result<Visibility> {
+		self.skip_ws();
+		if self.code[self.pos..].starts_with(b":::") {
+			self.pos += 3;
+			Ok(Visibility::Unhide)
+		} else if self.code[self.pos..].starts_with(b"::") {
+			self.pos += 2;
+			Ok(Visibility::Hidden)
+		} else if self.peek() == Some(b':') {
+			self.pos += 1;
+			Ok(Visibility::Normal)
+		} else {
+			Err(ParseError(format!("expected ':' at offset {}", self.pos)))
+		}
+	}
+
+	fn parse_params(&mut self) -> Result<ParamsDesc<'a>> {
+		self.expect_byte(b'(')?;
+		let mut params = Vec::new();
+		self.skip_ws();
+		if self.peek() == Some(b')') {
+			self.pos += 1;
+			return Ok(ParamsDesc(params));
+		}
+		loop {
+			let name = self.parse_ident()?;
+			self.skip_ws();
+			let default = if self.peek() == Some(b'=') {
+				self.pos += 1;
+				Some(self.parse_expr()?)
+			} else {
+				None
+			};
+			params.push(Param(name, default));
+			self.skip_ws();
+			match self.peek() {
+				Some(b',') => {
+					self.pos += 1;
+					self.skip_ws();
+					if self.peek() == Some(b')') {
+						self.pos += 1;
+						break;
+					}
+				}
+				Some(b')') => {
+					self.pos += 1;
+					break;
+				}
+				_ => {
+					return Err(ParseError(format!(
+						"expected ',' or ')' at offset {}",
+						self.pos
+					)))
+				}
+			}
+		}
+		Ok(ParamsDesc(params))
+	}
+
+	fn parse_args(&mut self) -> Result<ArgsDesc<'a>> {
+		self.expect_byte(b'(')?;
+		let mut args = Vec::new();
+		self.skip_ws();
+		if self.peek() == Some(b')') {
+			self.pos += 1;
+			return Ok(ArgsDesc(args));
+		}
+		loop {
+			self.skip_ws();
+			let save = self.pos;
+			let name = if self
+				.peek()
+				.map(|b| (b as char).is_ascii_alphabetic() || b == b'_')
+				.unwrap_or(false)
+			{
+				let ident = self.parse_ident()?;
+				self.skip_ws();
+				if self.peek() == Some(b'=') && self.code.get(self.pos + 1) != Some(&b'=') {
+					self.pos += 1;
+					Some(ident)
+				} else {
+					self.pos = save;
+					None
+				}
+			} else {
+				None
+			};
+			let value = self.parse_expr()?;
+			args.push(Arg(name, value));
+			self.skip_ws();
+			match self.peek() {
+				Some(b',') => {
+					self.pos += 1;
+					self.skip_ws();
+					if self.peek() == Some(b')') {
+						self.pos += 1;
+						break;
+					}
+				}
+				Some(b')') => {
+					self.pos += 1;
+					break;
+				}
+				_ => {
+					return Err(ParseError(format!(
+						"expected ',' or ')' at offset {}",
+						self.pos
+					)))
+				}
+			}
+		}
+		Ok(ArgsDesc(args))
+	}
+
+	fn parse_object(&mut self) -> Result<Vec<Member<'a>>> {
+		self.expect_byte(b'{')?;
+		let mut members = Vec::new();
+		self.skip_ws();
+		if self.peek() == Some(b'}') {
+			self.pos += 1;
+			return Ok(members);
+		}
+		loop {
+			self.skip_ws();
+			if self.eat_keyword("local") {
+				let name = self.parse_ident()?;
+				self.skip_ws();
+				let params = if self.peek() == Some(b'(') {
+					Some(self.parse_params()?)
+				} else {
+					None
+				};
+				self.expect_byte(b'=')?;
+				let value = self.parse_expr()?;
+				members.push(Member::BindStmt(BindSpec {
+					name,
+					params,
+					value,
+				}));
+			} else if self.eat_keyword("assert") {
+				let cond = self.parse_expr()?;
+				self.skip_ws();
+				let msg = if self.peek() == Some(b':') {
+					self.pos += 1;
+					Some(self.parse_expr()?)
+				} else {
+					None
+				};
+				members.push(Member::AssertStmt(AssertStmt(cond, msg)));
+			} else {
+				let name = self.parse_field_name()?;
+				self.skip_ws();
+				let params = if self.peek() == Some(b'(') {
+					Some(self.parse_params()?)
+				} else {
+					None
+				};
+				self.skip_ws();
+				let plus = if self.peek() == Some(b'+') {
+					self.pos += 1;
+					true
+				} else {
+					false
+				};
+				let visibility = self.parse_visibility()?;
+				let value = self.parse_expr()?;
+				members.push(Member::Field(FieldMember {
+					name,
+					plus,
+					params,
+					visibility,
+					value,
+				}));
+			}
+			self.skip_ws();
+			match self.peek() {
+				Some(b',') => {
+					self.pos += 1;
+					self.skip_ws();
+					if self.peek() == Some(b'}') {
+						self.pos += 1;
+						break;
+					}
+				}
+				Some(b'}') => {
+					self.pos += 1;
+					break;
+				}
+				_ => {
+					return Err(ParseError(format!(
+						"expected ',' or '}}' at offset {}",
+						self.pos
+					)))
+				}
+			}
+		}
+		Ok(members)
+	}
+
+	/// Parses the body of `[...]`: an empty/plain array, or - if the first
+	/// element is followed by `for` - an array comprehension.
+	fn parse_array_or_comp(&mut self) -> Result<Expr<'a>> {
+		self.expect_byte(b'[')?;
+		self.skip_ws();
+		if self.peek() == Some(b']') {
+			self.pos += 1;
+			return Ok(Expr::Arr(Vec::new()));
+		}
+		let first = self.parse_expr()?;
+		self.skip_ws();
+		if self.eat_keyword("for") {
+			let var = self.parse_ident()?;
+			self.expect_keyword("in")?;
+			let arr = self.parse_expr()?;
+			let first_spec = ForSpecData(var, arr);
+			let mut rest = Vec::new();
+			loop {
+				if self.eat_keyword("for") {
+					let var = self.parse_ident()?;
+					self.expect_keyword("in")?;
+					let arr = self.parse_expr()?;
+					rest.push(CompSpec::ForSpec(ForSpecData(var, arr)));
+				} else if self.eat_keyword("if") {
+					let cond = self.parse_expr()?;
+					rest.push(CompSpec::IfSpec(IfSpecData(cond)));
+				} else {
+					break;
+				}
+			}
+			self.expect_byte(b']')?;
+			return Ok(Expr::ArrComp(first, first_spec, rest));
+		}
+		let mut items = vec![first];
+		loop {
+			self.skip_ws();
+			match self.peek() {
+				Some(b',') => {
+					self.pos += 1;
+					self.skip_ws();
+					if self.peek() == Some(b']') {
+						self.pos += 1;
+						break;
+					}
+					items.push(self.parse_expr()?);
+				}
+				Some(b']') => {
+					self.pos += 1;
+					break;
+				}
+				_ => {
+					return Err(ParseError(format!(
+						"expected ',' or ']' at offset {}",
+						self.pos
+					)))
+				}
+			}
+		}
+		Ok(Expr::Arr(items))
+	}
+
+	fn parse_primary(&mut self) -> Result<LocExpr<'a>> {
+		self.skip_ws();
+		let start = self.pos;
+		let expr = match self.peek() {
+			Some(b'{') => Expr::Obj(ObjBody::MemberList(self.parse_object()?)),
+			Some(b'[') => self.parse_array_or_comp()?,
+			Some(b'(') => {
+				self.pos += 1;
+				let inner = self.parse_expr()?;
+				self.expect_byte(b')')?;
+				Expr::Parened(inner)
+			}
+			Some(b'"') | Some(b'\'') => Expr::Str(self.parse_string()?),
+			Some(b) if (b as char).is_ascii_digit() => Expr::Num(self.parse_number()?),
+			Some(b'$') => {
+				self.pos += 1;
+				Expr::Literal(LiteralType::Dollar)
+			}
+			Some(b) if (b as char).is_ascii_alphabetic() || b == b'_' => {
+				match self.parse_ident()?.as_str() {
+					"null" => Expr::Literal(LiteralType::Null),
+					"true" => Expr::Literal(LiteralType::True),
+					"false" => Expr::Literal(LiteralType::False),
+					"self" => Expr::Literal(LiteralType::This),
+					"super" => Expr::Literal(LiteralType::Super),
+					other => Expr::Var(other.to_owned()),
+				}
+			}
+			_ => {
+				return Err(ParseError(format!(
+					"unexpected character at offset {}",
+					self.pos
+				)))
+			}
+		};
+		let end = self.pos;
+		Ok(self.mk(expr, start, end))
+	}
+
+	/// Part of a `[start:end:step]` slice, or an `[index]`: `None` when the
+	/// part is omitted (`self.peek()` is `:` or `]`).
+	fn parse_slice_part(&mut self) -> Result<Option<LocExpr<'a>>> {
+		self.skip_ws();
+		if matches!(self.peek(), Some(b':') | Some(b']')) {
+			Ok(None)
+		} else {
+			Ok(Some(self.parse_expr()?))
+		}
+	}
+
+	/// `parse_primary` plus `.field`/`(args)`/`[index or slice]`/`{obj}`
+	/// (extension) trailers, left-associative.
+	fn parse_postfix(&mut self) -> Result<LocExpr<'a>> {
+		let start = self.pos;
+		let mut expr = self.parse_primary()?;
+		loop {
+			self.skip_ws();
+			match self.peek() {
+				Some(b'.') => {
+					self.pos += 1;
+					let field = self.parse_ident()?;
+					let end = self.pos;
+					expr = self.mk(Expr::Select(expr, field), start, end);
+				}
+				Some(b'(') => {
+					let args = self.parse_args()?;
+					let end = self.pos;
+					expr = self.mk(Expr::Apply(expr, args), start, end);
+				}
+				Some(b'[') => {
+					self.pos += 1;
+					let first = self.parse_slice_part()?;
+					self.skip_ws();
+					if self.peek() == Some(b':') {
+						self.pos += 1;
+						let end_part = self.parse_slice_part()?;
+						self.skip_ws();
+						let step = if self.peek() == Some(b':') {
+							self.pos += 1;
+							self.parse_slice_part()?
+						} else {
+							None
+						};
+						self.expect_byte(b']')?;
+						let end = self.pos;
+						expr = self.mk(
+							Expr::Slice(
+								expr,
+								SliceDesc {
+									start: first,
+									end: end_part,
+									step,
+								},
+							),
+							start,
+							end,
+						);
+					} else {
+						let idx = first.ok_or_else(|| {
+							ParseError(format!("expected index expression at offset {}", self.pos))
+						})?;
+						self.expect_byte(b']')?;
+						let end = self.pos;
+						expr = self.mk(Expr::Index(expr, idx), start, end);
+					}
+				}
+				Some(b'{') => {
+					let body = ObjBody::MemberList(self.parse_object()?);
+					let end = self.pos;
+					expr = self.mk(Expr::ObjExtend(expr, body), start, end);
+				}
+				_ => break,
+			}
+		}
+		Ok(expr)
+	}
+
+	fn peek_unary_op(&self) -> Option<UnaryOpType> {
+		match self.peek() {
+			Some(b'-') => Some(UnaryOpType::Minus),
+			Some(b'+') => Some(UnaryOpType::Plus),
+			Some(b'!') => Some(UnaryOpType::Not),
+			Some(b'~') => Some(UnaryOpType::BitNot),
+			_ => None,
+		}
+	}
+
+	fn parse_unary(&mut self) -> Result<LocExpr<'a>> {
+		self.skip_ws();
+		let start = self.pos;
+		if let Some(op) = self.peek_unary_op() {
+			self.pos += 1;
+			let inner = self.parse_unary()?;
+			let end = self.pos;
+			return Ok(self.mk(Expr::UnaryOp(op, inner), start, end));
+		}
+		self.parse_postfix()
+	}
+
+	/// Next binary operator token (not consumed) and its precedence - higher
+	/// binds tighter, matching the Jsonnet spec's `||`(1) < `&&`(2) < `|`(3)
+	/// < `^`(4) < `&`(5) < `==`/`!=`(6) < `<`/`>`/`<=`/`>=`/`in`(7) <
+	/// `<<`/`>>`(8) < `+`/`-`(9) < `*`/`/`/`%`(10) precedence table.
+	fn peek_binop(&self) -> Option<(BinaryOpType, u8, usize)> {
+		let rest = &self.code[self.pos..];
+		if rest.starts_with(b"||") {
+			return Some((BinaryOpType::Or, 1, 2));
+		}
+		if rest.starts_with(b"&&") {
+			return Some((BinaryOpType::And, 2, 2));
+		}
+		if rest.starts_with(b"==") {
+			return Some((BinaryOpType::Eq, 6, 2));
+		}
+		if rest.starts_with(b"!=") {
+			return Some((BinaryOpType::Ne, 6, 2));
+		}
+		if rest.starts_with(b"<=") {
+			return Some((BinaryOpType::Lte, 7, 2));
+		}
+		if rest.starts_with(b">=") {
+			return Some((BinaryOpType::Gte, 7, 2));
+		}
+		if rest.starts_with(b"<<") {
+			return Some((BinaryOpType::Lhs, 8, 2));
+		}
+		if rest.starts_with(b">>") {
+			return Some((BinaryOpType::Rhs, 8, 2));
+		}
+		match rest.first() {
+			Some(b'|') => Some((BinaryOpType::BitOr, 3, 1)),
+			Some(b'^') => Some((BinaryOpType::BitXor, 4, 1)),
+			Some(b'&') => Some((BinaryOpType::BitAnd, 5, 1)),
+			Some(b'<') => Some((BinaryOpType::Lt, 7, 1)),
+			Some(b'>') => Some((BinaryOpType::Gt, 7, 1)),
+			Some(b'+') => Some((BinaryOpType::Add, 9, 1)),
+			Some(b'-') => Some((BinaryOpType::Sub, 9, 1)),
+			Some(b'*') => Some((BinaryOpType::Mul, 10, 1)),
+			Some(b'/') => Some((BinaryOpType::Div, 10, 1)),
+			Some(b'%') => Some((BinaryOpType::Mod, 10, 1)),
+			_ => None,
+		}
+	}
+
+	/// Precedence-climbing binary operator parser; `min_prec` is the lowest
+	/// precedence this call is allowed to consume.
+	fn parse_binary(&mut self, min_prec: u8) -> Result<LocExpr<'a>> {
+		let start = self.pos;
+		let mut lhs = self.parse_unary()?;
+		loop {
+			self.skip_ws();
+			let (op, prec, len) = match self.peek_binop() {
+				Some(t) => t,
+				None if self.peek_keyword("in") => (BinaryOpType::In, 7, 2),
+				None => break,
+			};
+			if prec < min_prec {
+				break;
+			}
+			self.pos += len;
+			let rhs = self.parse_binary(prec + 1)?;
+			let end = self.pos;
+			lhs = self.mk(Expr::BinaryOp(lhs, op, rhs), start, end);
+		}
+		Ok(lhs)
+	}
+
+	fn parse_local(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("local")?;
+		let mut binds = Vec::new();
+		loop {
+			let name = self.parse_ident()?;
+			self.skip_ws();
+			let params = if self.peek() == Some(b'(') {
+				Some(self.parse_params()?)
+			} else {
+				None
+			};
+			self.expect_byte(b'=')?;
+			let value = self.parse_expr()?;
+			binds.push(BindSpec {
+				name,
+				params,
+				value,
+			});
+			self.skip_ws();
+			if self.peek() == Some(b',') {
+				self.pos += 1;
+				continue;
+			}
+			break;
+		}
+		self.expect_byte(b';')?;
+		let body = self.parse_expr()?;
+		let end = self.pos;
+		Ok(self.mk(Expr::LocalExpr(binds, body), start, end))
+	}
+
+	fn parse_if(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("if")?;
+		let cond = self.parse_expr()?;
+		self.expect_keyword("then")?;
+		let cond_then = self.parse_expr()?;
+		let cond_else = if self.eat_keyword("else") {
+			Some(self.parse_expr()?)
+		} else {
+			None
+		};
+		let end = self.pos;
+		Ok(self.mk(
+			Expr::IfElse {
+				cond: IfSpecData(cond),
+				cond_then,
+				cond_else,
+			},
+			start,
+			end,
+		))
+	}
+
+	fn parse_function(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("function")?;
+		let params = self.parse_params()?;
+		let body = self.parse_expr()?;
+		let end = self.pos;
+		Ok(self.mk(Expr::Function(params, body), start, end))
+	}
+
+	fn parse_assert_expr(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("assert")?;
+		let cond = self.parse_expr()?;
+		self.skip_ws();
+		let msg = if self.peek() == Some(b':') {
+			self.pos += 1;
+			Some(self.parse_expr()?)
+		} else {
+			None
+		};
+		self.expect_byte(b';')?;
+		let body = self.parse_expr()?;
+		let end = self.pos;
+		Ok(self.mk(Expr::AssertExpr(AssertStmt(cond, msg), body), start, end))
+	}
+
+	fn parse_import(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("import")?;
+		let path = self.parse_string()?;
+		let end = self.pos;
+		Ok(self.mk(Expr::Import(path), start, end))
+	}
+
+	fn parse_importstr(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("importstr")?;
+		let path = self.parse_string()?;
+		let end = self.pos;
+		Ok(self.mk(Expr::ImportStr(path), start, end))
+	}
+
+	fn parse_error(&mut self, start: usize) -> Result<LocExpr<'a>> {
+		self.expect_keyword("error")?;
+		let inner = self.parse_expr()?;
+		let end = self.pos;
+		Ok(self.mk(Expr::Error(inner), start, end))
+	}
+
+	fn parse_expr(&mut self) -> Result<LocExpr<'a>> {
+		self.skip_ws();
+		let start = self.pos;
+		if let Some(kw) = self.peek_ident() {
+			match kw.as_str() {
+				"local" => return self.parse_local(start),
+				"if" => return self.parse_if(start),
+				"function" => return self.parse_function(start),
+				"assert" => return self.parse_assert_expr(start),
+				"import" => return self.parse_import(start),
+				"importstr" => return self.parse_importstr(start),
+				"error" => return self.parse_error(start),
+				_ => {}
+			}
+		}
+		self.parse_binary(1)
+	}
+}
+
+#[cfg(all(test, not(feature = "rc-backend")))]
+mod tests {
+	use super::*;
+
+	fn parse_ok<'a>(code: &str, arena: &'a Arena) -> Expr<'a> {
+		let settings = ParserSettings {
+			file_name: "test.jsonnet".to_owned(),
+			loc_data: false,
+		};
+		parse(code, &settings, arena)
+			.unwrap_or_else(|e| panic!("failed to parse {:?}: {:?}", code, e))
+			.0
+			.clone()
+	}
+
+	#[test]
+	fn binary_precedence_matches_the_jsonnet_table() {
+		let arena = Arena::new();
+		// `*` binds tighter than `+`: `1 + 2 * 3` is `1 + (2 * 3)`, not
+		// `(1 + 2) * 3`.
+		match parse_ok("1 + 2 * 3", &arena) {
+			Expr::BinaryOp(_, BinaryOpType::Add, rhs) => {
+				assert!(matches!(rhs.0, Expr::BinaryOp(_, BinaryOpType::Mul, _)));
+			}
+			other => panic!("expected Add at the top, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn unary_minus_on_a_number_literal() {
+		let arena = Arena::new();
+		match parse_ok("-1", &arena) {
+			Expr::UnaryOp(UnaryOpType::Minus, inner) => {
+				assert_eq!(inner.0, Expr::Num(1.0));
+			}
+			other => panic!("expected UnaryOp(Minus, ..), got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn if_then_else() {
+		let arena = Arena::new();
+		match parse_ok("if true then 1 else 2", &arena) {
+			Expr::IfElse {
+				cond_then,
+				cond_else,
+				..
+			} => {
+				assert_eq!(cond_then.0, Expr::Num(1.0));
+				assert_eq!(cond_else.map(|e| e.0.clone()), Some(Expr::Num(2.0)));
+			}
+			other => panic!("expected IfElse, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn local_binding() {
+		let arena = Arena::new();
+		match parse_ok("local x = 1; x", &arena) {
+			Expr::LocalExpr(binds, body) => {
+				assert_eq!(binds.len(), 1);
+				assert_eq!(binds[0].name, "x");
+				assert_eq!(body.0, Expr::Var("x".to_owned()));
+			}
+			other => panic!("expected LocalExpr, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn select_index_and_call_chain() {
+		let arena = Arena::new();
+		match parse_ok("a.b[0](c)", &arena) {
+			Expr::Apply(callee, args) => {
+				assert_eq!(args.0.len(), 1);
+				assert!(matches!(callee.0, Expr::Index(_, _)));
+			}
+			other => panic!("expected Apply at the top, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn slice_with_omitted_parts() {
+		let arena = Arena::new();
+		match parse_ok("a[1:]", &arena) {
+			Expr::Slice(_, SliceDesc { start, end, step }) => {
+				assert!(start.is_some());
+				assert!(end.is_none());
+				assert!(step.is_none());
+			}
+			other => panic!("expected Slice, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn object_with_quoted_and_computed_keys() {
+		let arena = Arena::new();
+		match parse_ok(r#"{"a": 1, [ "b" ]: 2}"#, &arena) {
+			Expr::Obj(ObjBody::MemberList(members)) => {
+				assert_eq!(members.len(), 2);
+				assert!(matches!(
+					&members[0],
+					Member::Field(FieldMember {
+						name: FieldName::Fixed(n),
+						..
+					}) if n == "a"
+				));
+				assert!(matches!(
+					&members[1],
+					Member::Field(FieldMember {
+						name: FieldName::Dyn(_),
+						..
+					})
+				));
+			}
+			other => panic!("expected Obj, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn object_hidden_field_visibility() {
+		let arena = Arena::new();
+		match parse_ok("{a:: 1}", &arena) {
+			Expr::Obj(ObjBody::MemberList(members)) => match &members[0] {
+				Member::Field(FieldMember { visibility, .. }) => {
+					assert_eq!(*visibility, Visibility::Hidden);
+				}
+				other => panic!("expected a Field member, got {:?}", other),
+			},
+			other => panic!("expected Obj, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn array_comprehension() {
+		let arena = Arena::new();
+		match parse_ok("[x for x in [1, 2, 3] if x > 1]", &arena) {
+			Expr::ArrComp(_, ForSpecData(var, _), rest) => {
+				assert_eq!(var, "x");
+				assert_eq!(rest.len(), 1);
+				assert!(matches!(rest[0], CompSpec::IfSpec(_)));
+			}
+			other => panic!("expected ArrComp, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn function_literal_and_import_forms() {
+		let arena = Arena::new();
+		assert!(matches!(
+			parse_ok("function(x, y = 1) x + y", &arena),
+			Expr::Function(..)
+		));
+		assert!(matches!(parse_ok("import \"a.libsonnet\"", &arena), Expr::Import(p) if p == "a.libsonnet"));
+		assert!(matches!(parse_ok("importstr \"a.txt\"", &arena), Expr::ImportStr(p) if p == "a.txt"));
+		assert!(matches!(parse_ok("error \"oops\"", &arena), Expr::Error(_)));
+	}
+
+	#[test]
+	fn comments_are_skipped() {
+		let arena = Arena::new();
+		match parse_ok("1 + /* comment */ 2 // trailing\n", &arena) {
+			Expr::BinaryOp(lhs, BinaryOpType::Add, rhs) => {
+				assert_eq!(lhs.0, Expr::Num(1.0));
+				assert_eq!(rhs.0, Expr::Num(2.0));
+			}
+			other => panic!("expected BinaryOp(Add), got {:?}", other),
+		}
+	}
+}